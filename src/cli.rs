@@ -0,0 +1,91 @@
+use std::path::PathBuf;
+
+use clap::Parser;
+
+/// Intuitive find & replace for the command line.
+#[derive(Debug, Parser)]
+#[command(name = "sd", version, about)]
+pub(crate) struct Options {
+    /// Use fixed-string matching instead of regular expressions.
+    #[arg(short = 'F', long)]
+    pub literal_mode: bool,
+
+    /// Use the `fancy-regex` engine (lookaround, backreferences) instead of the default one.
+    #[arg(short = 'E', long = "fancy-regex")]
+    pub use_fancy_regex: bool,
+
+    /// Regex flags: `c`, `i`, `m`, `s`, `w`, `e`. Multiple flags can be passed at once, e.g. `-f ci`.
+    #[arg(short = 'f', long)]
+    pub flags: Option<String>,
+
+    /// Limit the number of replacements per file, or 0 for unlimited.
+    #[arg(short = 'n', long = "max-replacements", default_value_t = 0)]
+    pub replacements: usize,
+
+    /// Print the result to stdout instead of modifying files in place.
+    #[arg(short, long)]
+    pub preview: bool,
+
+    /// Print a unified diff of the changes instead of modifying files in place.
+    #[arg(long)]
+    pub diff: bool,
+
+    /// Only print the matched text, not the surrounding content.
+    #[arg(short, long = "only-matched")]
+    pub only_matched: bool,
+
+    /// Color the replacements (or, with `--diff`, the removed/added lines) in the output.
+    /// Output is never colored without this flag, even on a terminal.
+    #[arg(long)]
+    pub use_color: bool,
+
+    /// Recursively walk directory arguments, honoring `.gitignore`/`.ignore` files.
+    #[arg(short = 'r', long)]
+    pub recursive: bool,
+
+    /// Include hidden files and directories when walking recursively.
+    #[arg(long)]
+    pub hidden: bool,
+
+    /// Only search files of the given type (e.g. `rust`, `py`). Can be repeated.
+    #[arg(long = "type", value_name = "TYPE")]
+    pub type_filters: Vec<String>,
+
+    /// Skip files of the given type. Can be repeated.
+    #[arg(long = "type-not", value_name = "TYPE")]
+    pub type_not_filters: Vec<String>,
+
+    /// Include (or, prefixed with `!`, exclude) files matching a glob. Can be repeated;
+    /// later `--glob` flags take precedence over earlier ones.
+    #[arg(long, value_name = "GLOB")]
+    pub glob: Vec<String>,
+
+    /// Print the built-in table of recognized `--type` names and exit.
+    #[arg(long)]
+    pub type_list: bool,
+
+    /// Stream files record-by-record instead of buffering the whole file (and its output) in
+    /// memory. Auto-enabled for any file above roughly 1 MiB regardless of this flag.
+    #[arg(long)]
+    pub streaming: bool,
+
+    /// When streaming, assume the pattern can match across newlines (implied by the `s` flag).
+    #[arg(long)]
+    pub multiline: bool,
+
+    /// When streaming across newlines, how much unflushed tail to keep buffered so a match
+    /// straddling a flush boundary is never split.
+    #[arg(long, default_value_t = 64 * 1024)]
+    pub max_match_bytes: usize,
+
+    /// The regex (or literal string with -F) to search for.
+    #[arg(required_unless_present = "type_list", default_value = "")]
+    pub find: String,
+
+    /// The replacement string. Supports `$1`, `$name`, etc. capture references.
+    #[arg(default_value = "")]
+    pub replace_with: String,
+
+    /// Files (or, with -r, directories) to search & replace in. Reads from stdin if omitted.
+    pub files: Vec<PathBuf>,
+}
@@ -0,0 +1,247 @@
+use std::fmt::Write as _;
+
+/// One replaced region: `[start, end)` in the original content, and the bytes
+/// that replace it.
+pub struct Change {
+    pub start: usize,
+    pub end: usize,
+    pub replacement: Vec<u8>,
+}
+
+const CONTEXT_LINES: usize = 3;
+
+/// Render `changes` against `content` as a unified diff (`@@ -a,b +c,d @@`
+/// hunks with `-`/`+` lines), the way `git diff --no-index` would for an
+/// in-place edit. Returns `None` if there are no changes to show.
+pub fn unified_diff(
+    path: &str,
+    content: &[u8],
+    changes: &[Change],
+    use_color: bool,
+) -> Option<String> {
+    if changes.is_empty() {
+        return None;
+    }
+
+    let lines = line_spans(content);
+
+    // Replay the changes to build the new content, remembering the
+    // cumulative byte delta after each one so hunk boundaries in the
+    // original can be mapped onto the replaced content without re-scanning.
+    let mut new_content = Vec::with_capacity(content.len());
+    let mut deltas_after = Vec::with_capacity(changes.len());
+    let mut last = 0usize;
+    let mut delta: i64 = 0;
+    for change in changes {
+        new_content.extend_from_slice(&content[last..change.start]);
+        new_content.extend_from_slice(&change.replacement);
+        last = change.end;
+        delta += change.replacement.len() as i64 - (change.end as i64 - change.start as i64);
+        deltas_after.push(delta);
+    }
+    new_content.extend_from_slice(&content[last..]);
+
+    let change_spans: Vec<(usize, usize)> = changes
+        .iter()
+        .map(|c| {
+            let first = line_of(&lines, c.start);
+            let last = if c.end > c.start {
+                line_of(&lines, c.end - 1)
+            } else {
+                first
+            };
+            (first, last)
+        })
+        .collect();
+
+    struct Hunk {
+        first_change: usize,
+        last_change: usize,
+        lo: usize,
+        hi: usize,
+    }
+
+    let mut hunks: Vec<Hunk> = Vec::new();
+    for (i, &(first, last)) in change_spans.iter().enumerate() {
+        let lo = first.saturating_sub(CONTEXT_LINES);
+        let hi = (last + CONTEXT_LINES).min(lines.len() - 1);
+        match hunks.last_mut() {
+            Some(h) if lo <= h.hi + 1 => {
+                h.hi = h.hi.max(hi);
+                h.last_change = i;
+            }
+            _ => hunks.push(Hunk { first_change: i, last_change: i, lo, hi }),
+        }
+    }
+
+    let mut out = String::new();
+    writeln!(out, "--- a/{path}").ok()?;
+    writeln!(out, "+++ b/{path}").ok()?;
+
+    for hunk in &hunks {
+        let delta_before = if hunk.first_change == 0 { 0 } else { deltas_after[hunk.first_change - 1] };
+        let delta_through = deltas_after[hunk.last_change];
+
+        let old_start_byte = lines[hunk.lo].0;
+        let old_end_byte = lines[hunk.hi].1;
+        let new_start_byte = (old_start_byte as i64 + delta_before) as usize;
+        let new_end_byte = (old_end_byte as i64 + delta_through) as usize;
+
+        let old_line_count = hunk.hi - hunk.lo + 1;
+        let new_line_no = count_newlines(&new_content[..new_start_byte]) + 1;
+        let new_line_count = count_lines(&new_content[new_start_byte..new_end_byte]);
+
+        writeln!(
+            out,
+            "@@ -{},{} +{},{} @@",
+            hunk.lo + 1,
+            old_line_count,
+            new_line_no,
+            new_line_count
+        )
+        .ok()?;
+
+        // Walk the changes making up this hunk one at a time (rather than
+        // treating [first_change, last_change] as a single merged block), so
+        // unmodified lines between two nearby changes show up once as ` `
+        // context instead of as a matching `-`/`+` pair. Changes that share a
+        // line are rendered together, so that line is removed and added once.
+        let mut line_idx = hunk.lo;
+        let mut change_idx = hunk.first_change;
+        while change_idx <= hunk.last_change {
+            let block_start = change_idx;
+            let (change_first, mut change_last) = change_spans[change_idx];
+            while change_idx < hunk.last_change && change_spans[change_idx + 1].0 <= change_last {
+                change_idx += 1;
+                change_last = change_last.max(change_spans[change_idx].1);
+            }
+            for ctx in line_idx..change_first {
+                write_line(&mut out, " ", &content[lines[ctx].0..lines[ctx].1], false, use_color);
+            }
+
+            let change_delta_before =
+                if block_start == 0 { 0 } else { deltas_after[block_start - 1] };
+            let change_delta_through = deltas_after[change_idx];
+
+            let removed_start = lines[change_first].0;
+            let removed_end = lines[change_last].1;
+            for line in content[removed_start..removed_end].split_inclusive(|&b| b == b'\n') {
+                write_line(&mut out, "-", line, true, use_color);
+            }
+
+            let added_start = (removed_start as i64 + change_delta_before) as usize;
+            let added_end = (removed_end as i64 + change_delta_through) as usize;
+            for line in new_content[added_start..added_end].split_inclusive(|&b| b == b'\n') {
+                write_line(&mut out, "+", line, true, use_color);
+            }
+
+            line_idx = change_last + 1;
+            change_idx += 1;
+        }
+
+        for ctx in line_idx..=hunk.hi {
+            write_line(&mut out, " ", &content[lines[ctx].0..lines[ctx].1], false, use_color);
+        }
+    }
+
+    Some(out)
+}
+
+fn write_line(out: &mut String, prefix: &str, line: &[u8], colored: bool, use_color: bool) {
+    if line.is_empty() {
+        return;
+    }
+    let text = String::from_utf8_lossy(line);
+    let text = text.trim_end_matches(['\n', '\r']);
+    if use_color && colored {
+        let color = if prefix == "-" {
+            ansi_term::Color::Red
+        } else {
+            ansi_term::Color::Green
+        };
+        let _ = writeln!(out, "{prefix}{}{text}{}", color.prefix(), color.suffix());
+    } else {
+        let _ = writeln!(out, "{prefix}{text}");
+    }
+}
+
+fn line_spans(content: &[u8]) -> Vec<(usize, usize)> {
+    let mut spans = Vec::new();
+    let mut start = 0;
+    for (i, &b) in content.iter().enumerate() {
+        if b == b'\n' {
+            spans.push((start, i + 1));
+            start = i + 1;
+        }
+    }
+    if start < content.len() || spans.is_empty() {
+        spans.push((start, content.len()));
+    }
+    spans
+}
+
+fn line_of(lines: &[(usize, usize)], byte: usize) -> usize {
+    match lines.binary_search_by(|&(s, e)| {
+        if byte < s {
+            std::cmp::Ordering::Greater
+        } else if byte >= e {
+            std::cmp::Ordering::Less
+        } else {
+            std::cmp::Ordering::Equal
+        }
+    }) {
+        Ok(i) => i,
+        Err(i) => i.min(lines.len().saturating_sub(1)),
+    }
+}
+
+fn count_newlines(content: &[u8]) -> usize {
+    content.iter().filter(|&&b| b == b'\n').count()
+}
+
+fn count_lines(content: &[u8]) -> usize {
+    if content.is_empty() {
+        return 0;
+    }
+    let newlines = count_newlines(content);
+    if content.last() == Some(&b'\n') {
+        newlines
+    } else {
+        newlines + 1
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn two_changes_in_the_same_hunk_dont_duplicate_the_gap_between_them() {
+        // Two matches a couple of lines apart, well within CONTEXT_LINES of
+        // each other, so they land in a single merged hunk. The unmodified
+        // line between them must show up once as context, not once as `-`
+        // and once as an identical `+`.
+        let content = b"line1\nfoo\nline3\nfoo\nline5\n";
+        let changes = [
+            Change { start: 6, end: 9, replacement: b"bar".to_vec() },
+            Change { start: 16, end: 19, replacement: b"bar".to_vec() },
+        ];
+
+        let diff = unified_diff("f", content, &changes, false).unwrap();
+        let body: Vec<&str> = diff.lines().skip(2).collect();
+
+        assert_eq!(
+            body,
+            vec![
+                "@@ -1,5 +1,5 @@",
+                " line1",
+                "-foo",
+                "+bar",
+                " line3",
+                "-foo",
+                "+bar",
+                " line5",
+            ]
+        );
+    }
+}
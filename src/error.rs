@@ -0,0 +1,104 @@
+use std::{fmt, io, path::PathBuf};
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+/// Files that failed to be written back to disk, paired with the error that
+/// caused each failure.
+#[derive(Debug)]
+pub struct FailedJobs(pub Vec<(PathBuf, Error)>);
+
+impl fmt::Display for FailedJobs {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "failed to write {} file(s):", self.0.len())?;
+        for (path, e) in &self.0 {
+            writeln!(f, "  {}: {}", path.display(), e)?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug)]
+pub enum Error {
+    InvalidPath(PathBuf),
+    IoError(io::Error),
+    RegexError(regex::Error),
+    FancyRegexError(fancy_regex::Error),
+    InvalidCaptureRef(crate::replacer::InvalidReplaceCapture),
+    FailedJobs(FailedJobs),
+    UnknownType(String),
+    GlobError(globset::Error),
+    InvalidUtf8(std::str::Utf8Error),
+    InvalidUtf8String(std::string::FromUtf8Error),
+    PersistError(tempfile::PersistError),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::InvalidPath(p) => write!(f, "'{}' is not a valid path", p.display()),
+            Error::IoError(e) => write!(f, "{e}"),
+            Error::RegexError(e) => write!(f, "{e}"),
+            Error::FancyRegexError(e) => write!(f, "{e}"),
+            Error::InvalidCaptureRef(e) => write!(f, "{e}"),
+            Error::FailedJobs(e) => write!(f, "{e}"),
+            Error::UnknownType(name) => write!(
+                f,
+                "unrecognized type '{name}', run `sd --type-list` to see the supported types"
+            ),
+            Error::GlobError(e) => write!(f, "{e}"),
+            Error::InvalidUtf8(e) => write!(f, "{e}"),
+            Error::InvalidUtf8String(e) => write!(f, "{e}"),
+            Error::PersistError(e) => write!(f, "{e}"),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl From<io::Error> for Error {
+    fn from(e: io::Error) -> Self {
+        Error::IoError(e)
+    }
+}
+
+impl From<regex::Error> for Error {
+    fn from(e: regex::Error) -> Self {
+        Error::RegexError(e)
+    }
+}
+
+impl From<fancy_regex::Error> for Error {
+    fn from(e: fancy_regex::Error) -> Self {
+        Error::FancyRegexError(e)
+    }
+}
+
+impl From<crate::replacer::InvalidReplaceCapture> for Error {
+    fn from(e: crate::replacer::InvalidReplaceCapture) -> Self {
+        Error::InvalidCaptureRef(e)
+    }
+}
+
+impl From<globset::Error> for Error {
+    fn from(e: globset::Error) -> Self {
+        Error::GlobError(e)
+    }
+}
+
+impl From<std::str::Utf8Error> for Error {
+    fn from(e: std::str::Utf8Error) -> Self {
+        Error::InvalidUtf8(e)
+    }
+}
+
+impl From<std::string::FromUtf8Error> for Error {
+    fn from(e: std::string::FromUtf8Error) -> Self {
+        Error::InvalidUtf8String(e)
+    }
+}
+
+impl From<tempfile::PersistError> for Error {
+    fn from(e: tempfile::PersistError) -> Self {
+        Error::PersistError(e)
+    }
+}
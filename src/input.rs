@@ -0,0 +1,136 @@
+use std::{
+    fs,
+    io::{self, Write},
+    path::PathBuf,
+};
+
+use ignore::WalkBuilder;
+use memmap2::Mmap;
+
+use sd::Result;
+
+use crate::types::PathFilter;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum Source {
+    File(PathBuf),
+    Stdin,
+}
+
+impl Source {
+    pub(crate) fn from_stdin() -> Vec<Source> {
+        vec![Source::Stdin]
+    }
+
+    /// Build the list of files to operate on from explicit file/directory
+    /// arguments. When `recursive` is set, directories are walked the way
+    /// ripgrep walks them: `.gitignore`, `.ignore`, and global ignore files
+    /// are honored at each level, with deeper files inheriting (and able to
+    /// override, via `!pattern`) the rules of their parent directories, and
+    /// hidden dotfiles are skipped unless `hidden` is set.
+    ///
+    /// `filter` is applied to every discovered (or explicitly named) path
+    /// before it's turned into a `Source`, so only matching files ever get
+    /// mmapped.
+    pub(crate) fn from_paths(
+        paths: Vec<PathBuf>,
+        recursive: bool,
+        hidden: bool,
+        filter: &PathFilter,
+    ) -> Vec<Source> {
+        if !recursive {
+            return paths
+                .into_iter()
+                .filter(|path| filter.matches(path))
+                .map(Source::File)
+                .collect();
+        }
+
+        let mut files = Vec::new();
+        let mut paths = paths.into_iter();
+        let Some(first) = paths.next() else {
+            return files;
+        };
+
+        let mut builder = WalkBuilder::new(first);
+        for path in paths {
+            builder.add(path);
+        }
+        builder.hidden(!hidden).git_ignore(true).git_global(true).ignore(true);
+
+        for entry in builder.build().filter_map(|e| e.ok()) {
+            if entry.file_type().is_some_and(|t| t.is_file()) && filter.matches(entry.path()) {
+                files.push(Source::File(entry.into_path()));
+            }
+        }
+
+        files
+    }
+
+    pub(crate) fn display(&self) -> String {
+        match self {
+            Source::File(path) => path.display().to_string(),
+            Source::Stdin => "<stdin>".to_owned(),
+        }
+    }
+}
+
+pub(crate) unsafe fn make_mmap(path: &PathBuf) -> Result<Mmap> {
+    let file = fs::File::open(path)?;
+    Ok(Mmap::map(&file)?)
+}
+
+/// stdin isn't seekable, so buffer it into a temp file first and mmap that,
+/// keeping the rest of the pipeline (which operates on `Mmap`s) unchanged.
+pub(crate) fn make_mmap_stdin() -> Result<Mmap> {
+    let mut temp = tempfile::tempfile()?;
+    io::copy(&mut io::stdin().lock(), &mut temp)?;
+    temp.flush()?;
+    Ok(unsafe { Mmap::map(&temp)? })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// `git_ignore` is only honored inside an actual git repository (see
+    /// `ignore::WalkBuilder::require_git`, on by default), so the temp
+    /// directory needs a real (if empty) `.git` to exercise it.
+    fn walked_names(root: &std::path::Path, hidden: bool) -> Vec<String> {
+        let filter = PathFilter::new(&[], &[], &[]).unwrap();
+        let mut names: Vec<String> = Source::from_paths(vec![root.to_path_buf()], true, hidden, &filter)
+            .iter()
+            .map(|s| match s {
+                Source::File(p) => p.strip_prefix(root).unwrap().display().to_string(),
+                Source::Stdin => unreachable!("from_paths never returns Source::Stdin"),
+            })
+            .collect();
+        names.sort();
+        names
+    }
+
+    #[test]
+    fn recursive_walk_honors_gitignore_negation_and_hidden_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        std::process::Command::new("git").arg("init").arg("-q").arg(root).status().unwrap();
+        fs::write(root.join(".gitignore"), "ignored.txt\nimportant.txt\n!keep/important.txt\n").unwrap();
+        fs::create_dir(root.join("keep")).unwrap();
+        fs::write(root.join("ignored.txt"), "x").unwrap();
+        fs::write(root.join("kept.txt"), "x").unwrap();
+        fs::write(root.join(".hidden.txt"), "x").unwrap();
+        fs::write(root.join("keep/important.txt"), "x").unwrap();
+        fs::write(root.join("keep/ignored.txt"), "x").unwrap();
+
+        // Ignored files stay out (even the one nested under `keep/`, inheriting
+        // the root `.gitignore`), the negated override is kept, and the hidden
+        // dotfile is skipped without `--hidden`.
+        assert_eq!(
+            walked_names(root, false),
+            ["keep/important.txt", "kept.txt"]
+        );
+
+        // `--hidden` surfaces the dotfile too; `.gitignore` rules are unaffected.
+        assert!(walked_names(root, true).contains(&".hidden.txt".to_owned()));
+    }
+}
@@ -0,0 +1,153 @@
+//! The substitution engine behind the `sd` CLI: literal/regex escaping, `\n`
+//! unescaping, `w`/`s`/`i` flag handling, replacement limits, and coloring,
+//! exposed so other programs can embed `sd`'s exact matching and replacement
+//! behavior instead of re-implementing it.
+//!
+//! ```no_run
+//! use sd::Replacement;
+//!
+//! let replacement = Replacement::builder()
+//!     .find("foo")
+//!     .replace_with("bar")
+//!     .build()?;
+//! assert_eq!(replacement.replace_str("foo foo")?, "bar bar");
+//! # Ok::<(), sd::Error>(())
+//! ```
+
+mod error;
+
+pub mod diff;
+pub mod replacer;
+
+pub use error::{Error, FailedJobs, Result};
+pub use replacer::{InvalidReplaceCapture, Replacer};
+
+use std::borrow::Cow;
+
+use replacer::{FancyReplacer, RegexReplacer};
+
+enum Engine {
+    Regex(RegexReplacer),
+    Fancy(FancyReplacer),
+}
+
+/// A configured find/replace operation, built via [`Replacement::builder`].
+pub struct Replacement {
+    engine: Engine,
+}
+
+impl Replacement {
+    pub fn builder() -> ReplacementBuilder {
+        ReplacementBuilder::default()
+    }
+
+    /// Replace every match in `content` (at most [`limit`](ReplacementBuilder::limit)
+    /// of them, if set), returning it unchanged (borrowed) if nothing matched.
+    ///
+    /// The `fancy` engine matches on `str`, so non-UTF-8 `content` is rejected
+    /// with [`Error::InvalidUtf8`] rather than matched against garbage.
+    pub fn replace_bytes<'a>(&'a self, content: &'a [u8]) -> Result<Cow<'a, [u8]>> {
+        match &self.engine {
+            Engine::Regex(r) => Ok(r
+                .replace(content, false, false)
+                .unwrap_or(Cow::Borrowed(content))),
+            Engine::Fancy(r) => {
+                let content_str = str::from_utf8(content)?;
+                match r.replace(content_str, false, false) {
+                    Some(replaced) => Ok(Cow::Owned(replaced.into_owned().into_bytes())),
+                    None => Ok(Cow::Borrowed(content)),
+                }
+            }
+        }
+    }
+
+    /// Replace every match in `content` (at most [`limit`](ReplacementBuilder::limit)
+    /// of them, if set), returning it unchanged (borrowed) if nothing matched.
+    ///
+    /// A byte-mode `find` pattern on the `regex` engine can match a range
+    /// that splits a multi-byte character; that case is rejected with
+    /// [`Error::InvalidUtf8String`] rather than producing a `str` that isn't
+    /// valid UTF-8.
+    pub fn replace_str<'a>(&'a self, content: &'a str) -> Result<Cow<'a, str>> {
+        match &self.engine {
+            Engine::Regex(r) => match r.replace(content.as_bytes(), false, false) {
+                Some(replaced) => Ok(Cow::Owned(String::from_utf8(replaced.into_owned())?)),
+                None => Ok(Cow::Borrowed(content)),
+            },
+            Engine::Fancy(r) => Ok(r
+                .replace(content, false, false)
+                .unwrap_or(Cow::Borrowed(content))),
+        }
+    }
+}
+
+/// Builder for a [`Replacement`]. See [`Replacement::builder`].
+#[derive(Default)]
+pub struct ReplacementBuilder {
+    find: String,
+    replace_with: String,
+    literal: bool,
+    fancy: bool,
+    flags: Option<String>,
+    limit: usize,
+}
+
+impl ReplacementBuilder {
+    /// The pattern (or literal string, with [`literal`](Self::literal)) to search for.
+    pub fn find(mut self, find: impl Into<String>) -> Self {
+        self.find = find.into();
+        self
+    }
+
+    /// The replacement string. Supports `$1`, `$name`, etc. capture references unless
+    /// [`literal`](Self::literal) is set.
+    pub fn replace_with(mut self, replace_with: impl Into<String>) -> Self {
+        self.replace_with = replace_with.into();
+        self
+    }
+
+    /// Treat `find` as a fixed string instead of a regex.
+    pub fn literal(mut self, literal: bool) -> Self {
+        self.literal = literal;
+        self
+    }
+
+    /// Use the `fancy-regex` engine (lookaround, backreferences) instead of the default one.
+    pub fn fancy(mut self, fancy: bool) -> Self {
+        self.fancy = fancy;
+        self
+    }
+
+    /// Regex flags: `c`, `i`, `m`, `s`, `w`, `e`.
+    pub fn flags(mut self, flags: impl Into<String>) -> Self {
+        self.flags = Some(flags.into());
+        self
+    }
+
+    /// Limit the number of replacements, or 0 for unlimited.
+    pub fn limit(mut self, limit: usize) -> Self {
+        self.limit = limit;
+        self
+    }
+
+    pub fn build(self) -> Result<Replacement> {
+        let engine = if self.fancy {
+            Engine::Fancy(FancyReplacer::new(
+                self.find,
+                self.replace_with,
+                self.literal,
+                self.flags,
+                self.limit,
+            )?)
+        } else {
+            Engine::Regex(RegexReplacer::new(
+                self.find,
+                self.replace_with,
+                self.literal,
+                self.flags,
+                self.limit,
+            )?)
+        };
+        Ok(Replacement { engine })
+    }
+}
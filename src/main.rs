@@ -1,13 +1,12 @@
 mod cli;
-mod error;
 mod input;
-
-pub(crate) mod replacer;
+mod types;
 
 use clap::Parser;
 use memmap2::{Mmap, MmapMut};
 use rayon::iter::{IntoParallelRefIterator as _, ParallelIterator as _};
 use std::{
+    borrow::Cow,
     fs,
     io::{stdout, Write},
     ops::DerefMut,
@@ -15,10 +14,24 @@ use std::{
     process,
 };
 
-pub(crate) use self::error::{Error, FailedJobs, Result};
+use sd::replacer::{self, FancyReplacer, RegexReplacer, Replacer};
+use sd::{diff, Error, FailedJobs, Result};
+
 pub(crate) use self::input::Source;
 use self::input::{make_mmap, make_mmap_stdin};
-use self::replacer::{FancyReplacer, RegexReplacer, Replacer};
+use self::types::PathFilter;
+
+/// Files larger than this are streamed record-by-record even without `--streaming`, since
+/// buffering both the content and the fully-substituted output would otherwise be required.
+const STREAMING_THRESHOLD: usize = 1024 * 1024;
+
+fn should_stream(streaming: bool, len: usize) -> bool {
+    streaming || len > STREAMING_THRESHOLD
+}
+
+fn is_multiline(options: &cli::Options) -> bool {
+    options.multiline || options.flags.as_deref().is_some_and(|f| f.contains('s'))
+}
 
 fn main() {
     if let Err(e) = try_main() {
@@ -30,6 +43,11 @@ fn main() {
 fn try_main() -> Result<()> {
     let options = cli::Options::parse();
 
+    if options.type_list {
+        types::print_type_list(&mut stdout().lock())?;
+        return Ok(());
+    }
+
     if options.literal_mode && !options.replace_with.is_empty() {
         eprintln!("error: -F and -R are mutually exclusive");
         process::exit(1);
@@ -47,7 +65,18 @@ fn try_main() -> Result<()> {
     }
 }
 
+/// Per-file outcome of the (parallel) buffered replace pass in [`fancy_main`].
+enum FancyOutcome<'a> {
+    NoMatch,
+    Buffered(Cow<'a, str>),
+    Stream,
+}
+
 fn fancy_main(options: cli::Options) -> Result<()> {
+    let multiline = is_multiline(&options);
+    let max_match_bytes = options.max_match_bytes;
+    let streaming = options.streaming;
+
     let replacer = FancyReplacer::new(
         options.find,
         options.replace_with,
@@ -56,10 +85,12 @@ fn fancy_main(options: cli::Options) -> Result<()> {
         options.replacements,
     )?;
 
+    let filter = PathFilter::new(&options.type_filters, &options.type_not_filters, &options.glob)?;
+
     let sources = if options.files.is_empty() {
         Source::from_stdin()
     } else {
-        Source::from_paths(options.files)
+        Source::from_paths(options.files, options.recursive, options.hidden, &filter)
     };
 
     let mmaps: Vec<Mmap> = sources
@@ -77,46 +108,86 @@ fn fancy_main(options: cli::Options) -> Result<()> {
         })
         .collect();
 
-    let replaced: Vec<_> = {
-        mmaps
-            .par_iter()
-            .filter_map(|mmap| {
-                if mmap.len() > 1024 * 1024 {
-                    eprintln!("error: file is TOO LARGE! Currently we need to copy the whole file to memory, and may cause performance issues.");
-                }
-                let content = unsafe {str::from_utf8_unchecked(&mmap)};
-                replacer.replace(content, options.only_matched, options.use_color)
-            })
-            .collect()
-    };
+    if options.diff {
+        let mut handle = stdout().lock();
+        for (source, mmap) in sources.iter().zip(mmaps.iter()) {
+            let content = unsafe { str::from_utf8_unchecked(mmap) };
+            let changes = replacer.changes(content);
+            if let Some(rendered) =
+                diff::unified_diff(&source.display(), content.as_bytes(), &changes, options.use_color)
+            {
+                handle.write_all(rendered.as_bytes())?;
+            }
+        }
+        return Ok(());
+    }
+
+    let outcomes: Vec<FancyOutcome> = mmaps
+        .par_iter()
+        .map(|mmap| {
+            if should_stream(streaming, mmap.len()) {
+                return FancyOutcome::Stream;
+            }
+            let content = unsafe { str::from_utf8_unchecked(mmap) };
+            match replacer.replace(content, options.only_matched, options.use_color) {
+                Some(replaced) => FancyOutcome::Buffered(replaced),
+                None => FancyOutcome::NoMatch,
+            }
+        })
+        .collect();
 
     if options.preview || sources.first() == Some(&Source::Stdin) {
         let mut handle = stdout().lock();
 
-        for (source, replaced) in sources.iter().zip(replaced) {
+        for ((source, mmap), outcome) in sources.iter().zip(mmaps.iter()).zip(outcomes) {
             if sources.len() > 1 {
                 writeln!(handle, "----- {} -----", source.display())?;
             }
-            handle.write_all(replaced.as_bytes())?;
+            match outcome {
+                FancyOutcome::NoMatch => {}
+                FancyOutcome::Buffered(replaced) => handle.write_all(replaced.as_bytes())?,
+                FancyOutcome::Stream => {
+                    let content = unsafe { str::from_utf8_unchecked(mmap) };
+                    replacer::stream_str(
+                        &replacer,
+                        content,
+                        &mut handle,
+                        multiline,
+                        max_match_bytes,
+                        options.replacements,
+                        options.only_matched,
+                        options.use_color,
+                    )?;
+                }
+            }
         }
     } else {
-        // Windows requires closing mmap before writing:
-        // > The requested operation cannot be performed on a file with a user-mapped section open
-        #[cfg(target_family = "windows")]
-        let replaced: Vec<Vec<u8>> =
-            replaced.into_iter().map(|r| r.to_vec()).collect();
-        #[cfg(target_family = "windows")]
-        drop(mmaps);
-
         let mut failed_jobs = Vec::new();
-        for (source, replaced) in sources.iter().zip(replaced) {
-            match source {
-                Source::File(path) => {
-                    if let Err(e) = write_with_temp(path, replaced.as_bytes()) {
-                        failed_jobs.push((path.to_owned(), e));
-                    }
-                }
-                _ => unreachable!("stdin should go previous branch"),
+        for ((source, mmap), outcome) in sources.iter().zip(mmaps.iter()).zip(outcomes) {
+            let Source::File(path) = source else {
+                unreachable!("stdin should go previous branch")
+            };
+            let result = match outcome {
+                FancyOutcome::NoMatch => Ok(()),
+                FancyOutcome::Buffered(replaced) => write_with_temp(path, replaced.as_bytes()),
+                // NB: this reads from the source mmap while the temp file is being
+                // written, so the mmap stays open until after `persist`.
+                FancyOutcome::Stream => write_streamed_temp(path, |writer| {
+                    let content = unsafe { str::from_utf8_unchecked(mmap) };
+                    replacer::stream_str(
+                        &replacer,
+                        content,
+                        writer,
+                        multiline,
+                        max_match_bytes,
+                        options.replacements,
+                        options.only_matched,
+                        options.use_color,
+                    )
+                }),
+            };
+            if let Err(e) = result {
+                failed_jobs.push((path.to_owned(), e));
             }
         }
         if !failed_jobs.is_empty() {
@@ -127,7 +198,18 @@ fn fancy_main(options: cli::Options) -> Result<()> {
     Ok(())
 }
 
+/// Per-file outcome of the (parallel) buffered replace pass in [`regex_main`].
+enum RegexOutcome<'a> {
+    NoMatch,
+    Buffered(Cow<'a, [u8]>),
+    Stream,
+}
+
 fn regex_main(options: cli::Options) -> Result<()> {
+    let multiline = is_multiline(&options);
+    let max_match_bytes = options.max_match_bytes;
+    let streaming = options.streaming;
+
     let replacer = RegexReplacer::new(
         options.find,
         options.replace_with,
@@ -136,10 +218,12 @@ fn regex_main(options: cli::Options) -> Result<()> {
         options.replacements,
     )?;
 
+    let filter = PathFilter::new(&options.type_filters, &options.type_not_filters, &options.glob)?;
+
     let sources = if options.files.is_empty() {
         Source::from_stdin()
     } else {
-        Source::from_paths(options.files)
+        Source::from_paths(options.files, options.recursive, options.hidden, &filter)
     };
 
     let mmaps: Vec<Mmap> = sources
@@ -157,40 +241,78 @@ fn regex_main(options: cli::Options) -> Result<()> {
         })
         .collect();
 
-    let replaced: Vec<_> = mmaps
+    if options.diff {
+        let mut handle = stdout().lock();
+        for (source, mmap) in sources.iter().zip(mmaps.iter()) {
+            let changes = replacer.changes(mmap);
+            if let Some(rendered) = diff::unified_diff(&source.display(), mmap, &changes, options.use_color) {
+                handle.write_all(rendered.as_bytes())?;
+            }
+        }
+        return Ok(());
+    }
+
+    let outcomes: Vec<RegexOutcome> = mmaps
         .par_iter()
-        .filter_map(|mmap| {
-            replacer.replace(&mmap, options.only_matched, options.use_color)
+        .map(|mmap| {
+            if should_stream(streaming, mmap.len()) {
+                return RegexOutcome::Stream;
+            }
+            match replacer.replace(mmap, options.only_matched, options.use_color) {
+                Some(replaced) => RegexOutcome::Buffered(replaced),
+                None => RegexOutcome::NoMatch,
+            }
         })
         .collect();
 
     if options.preview || sources.first() == Some(&Source::Stdin) {
         let mut handle = stdout().lock();
 
-        for (source, replaced) in sources.iter().zip(replaced) {
+        for ((source, mmap), outcome) in sources.iter().zip(mmaps.iter()).zip(outcomes) {
             if sources.len() > 1 {
                 writeln!(handle, "----- {} -----", source.display())?;
             }
-            handle.write_all(&replaced)?;
+            match outcome {
+                RegexOutcome::NoMatch => {}
+                RegexOutcome::Buffered(replaced) => handle.write_all(&replaced)?,
+                RegexOutcome::Stream => replacer::stream_bytes(
+                    &replacer,
+                    mmap,
+                    &mut handle,
+                    multiline,
+                    max_match_bytes,
+                    options.replacements,
+                    options.only_matched,
+                    options.use_color,
+                )?,
+            }
         }
     } else {
-        // Windows requires closing mmap before writing:
-        // > The requested operation cannot be performed on a file with a user-mapped section open
-        #[cfg(target_family = "windows")]
-        let replaced: Vec<Vec<u8>> =
-            replaced.into_iter().map(|r| r.to_vec()).collect();
-        #[cfg(target_family = "windows")]
-        drop(mmaps);
-
         let mut failed_jobs = Vec::new();
-        for (source, replaced) in sources.iter().zip(replaced) {
-            match source {
-                Source::File(path) => {
-                    if let Err(e) = write_with_temp(path, &replaced) {
-                        failed_jobs.push((path.to_owned(), e));
-                    }
-                }
-                _ => unreachable!("stdin should go previous branch"),
+        for ((source, mmap), outcome) in sources.iter().zip(mmaps.iter()).zip(outcomes) {
+            let Source::File(path) = source else {
+                unreachable!("stdin should go previous branch")
+            };
+            let result = match outcome {
+                RegexOutcome::NoMatch => Ok(()),
+                RegexOutcome::Buffered(replaced) => write_with_temp(path, &replaced),
+                // NB: this reads from the source mmap while the temp file is being
+                // written, so the mmap stays open until after `persist`.
+                RegexOutcome::Stream => write_streamed_temp(path, |writer| {
+                    replacer::stream_bytes(
+                        &replacer,
+                        mmap,
+                        writer,
+                        multiline,
+                        max_match_bytes,
+                        options.replacements,
+                        options.only_matched,
+                        options.use_color,
+                    )
+                }),
+            };
+            if let Err(e) = result {
+                failed_jobs.push((path.to_owned(), e));
             }
         }
         if !failed_jobs.is_empty() {
@@ -225,3 +347,28 @@ fn write_with_temp(path: &PathBuf, data: &[u8]) -> Result<()> {
 
     Ok(())
 }
+
+/// Like [`write_with_temp`], but for the streaming path: `write` is handed the temp file
+/// directly and writes its output incrementally, instead of being given the whole result
+/// up front.
+fn write_streamed_temp(
+    path: &PathBuf,
+    write: impl FnOnce(&mut fs::File) -> Result<()>,
+) -> Result<()> {
+    let path = fs::canonicalize(path)?;
+
+    let mut temp = tempfile::NamedTempFile::new_in(
+        path.parent()
+            .ok_or_else(|| Error::InvalidPath(path.to_path_buf()))?,
+    )?;
+
+    if let Ok(metadata) = fs::metadata(&path) {
+        temp.as_file().set_permissions(metadata.permissions()).ok();
+    }
+
+    write(temp.as_file_mut())?;
+    temp.flush()?;
+    temp.persist(&path)?;
+
+    Ok(())
+}
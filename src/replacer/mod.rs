@@ -2,13 +2,21 @@ use std::borrow::Cow;
 
 use crate::Result;
 
+// Bring `replace_append` into scope for `NoExpand`/`&[u8]`/`&str` below without
+// binding the name `Replacer`, which would collide with the trait of the same
+// name defined in this module.
+use fancy_regex::Replacer as _;
+use regex::bytes::Replacer as _;
+
 #[cfg(test)]
 mod tests;
+mod stream;
 mod validate;
 
 pub use validate::{validate_replace, InvalidReplaceCapture};
+pub use stream::{stream_bytes, stream_str};
 
-pub(crate) trait Replacer<T: ?Sized + ToOwned> {
+pub trait Replacer<T: ?Sized + ToOwned> {
     fn new(
         look_for: String,
         replace_with: String,
@@ -25,9 +33,22 @@ pub(crate) trait Replacer<T: ?Sized + ToOwned> {
         only_matched: bool,
         use_color: bool,
     ) -> Option<Cow<'a, T>>;
+
+    /// Like [`Self::replace`], but draws from a shared `remaining` replacement
+    /// budget instead of this replacer's own `-n` limit, decrementing it by
+    /// however many replacements this call makes (and making none once it
+    /// hits 0). Used by the streaming path so `-n` caps replacements across a
+    /// whole file's chunks instead of being reapplied to each chunk on its own.
+    fn replace_bounded<'a>(
+        &'a self,
+        content: &'a T,
+        only_matched: bool,
+        use_color: bool,
+        remaining: &mut usize,
+    ) -> Option<Cow<'a, T>>;
 }
 
-pub(crate) struct RegexReplacer {
+pub struct RegexReplacer {
     regex: regex::bytes::Regex,
     replace_with: Vec<u8>,
     is_literal: bool,
@@ -117,12 +138,78 @@ impl Replacer<[u8]> for RegexReplacer {
                 only_matched,
             )
         }
+        .map(|(replaced, _made)| replaced)
+    }
+
+    fn replace_bounded<'a>(
+        &'a self,
+        content: &'a [u8],
+        only_matched: bool,
+        use_color: bool,
+        remaining: &mut usize,
+    ) -> Option<Cow<'a, [u8]>> {
+        if *remaining == 0 {
+            return None;
+        }
+        let regex = &self.regex;
+        let limit = *remaining;
+        let result = if self.is_literal {
+            RegexReplacer::replacen(
+                regex,
+                limit,
+                content,
+                use_color,
+                regex::bytes::NoExpand(&self.replace_with),
+                only_matched,
+            )
+        } else {
+            RegexReplacer::replacen(
+                regex,
+                limit,
+                content,
+                use_color,
+                &*self.replace_with,
+                only_matched,
+            )
+        };
+        if let Some((_, made)) = &result {
+            *remaining -= made;
+        }
+        result.map(|(replaced, _made)| replaced)
     }
 }
 
 impl RegexReplacer {
+    /// Like [`Replacer::replace`], but returns each match's byte range and
+    /// replacement individually instead of the fully-substituted content, for
+    /// `--diff` to render as a unified diff.
+    pub fn changes(&self, haystack: &[u8]) -> Vec<crate::diff::Change> {
+        let limit = self.replacements;
+        let mut changes = Vec::new();
+        for (i, cap) in self.regex.captures_iter(haystack).enumerate() {
+            let m = cap.get(0).unwrap();
+            let mut replacement = Vec::new();
+            if self.is_literal {
+                regex::bytes::NoExpand(&self.replace_with).replace_append(&cap, &mut replacement);
+            } else {
+                (&*self.replace_with).replace_append(&cap, &mut replacement);
+            }
+            changes.push(crate::diff::Change {
+                start: m.start(),
+                end: m.end(),
+                replacement,
+            });
+            if limit > 0 && i >= limit - 1 {
+                break;
+            }
+        }
+        changes
+    }
+
     /// A modified form of [`regex::bytes::Regex::replacen`] that supports
-    /// coloring replacements
+    /// coloring replacements. Returns the replaced content alongside the
+    /// number of replacements actually made, so callers sharing a budget
+    /// across calls (see [`Replacer::replace_bounded`]) can track it down.
     fn replacen<'haystack, R: regex::bytes::Replacer>(
         regex: &regex::bytes::Regex,
         limit: usize,
@@ -130,11 +217,12 @@ impl RegexReplacer {
         use_color: bool,
         mut rep: R,
         only_matched: bool,
-    ) -> Option<Cow<'haystack, [u8]>> {
+    ) -> Option<(Cow<'haystack, [u8]>, usize)> {
         let mut it = regex.captures_iter(haystack).enumerate().peekable();
         _ = it.peek()?;
         let mut new = Vec::with_capacity(haystack.len());
         let mut last_match = 0;
+        let mut made = 0;
         for (i, cap) in it {
             // unwrap on 0 is OK because captures only reports matches
             let m = cap.get(0).unwrap();
@@ -153,6 +241,7 @@ impl RegexReplacer {
                 );
             }
             last_match = m.end();
+            made = i + 1;
             if limit > 0 && i >= limit - 1 {
                 break;
             }
@@ -160,11 +249,11 @@ impl RegexReplacer {
         if !only_matched {
             new.extend_from_slice(&haystack[last_match..]);
         }
-        Some(Cow::Owned(new))
+        Some((Cow::Owned(new), made))
     }
 }
 
-pub(crate) struct FancyReplacer {
+pub struct FancyReplacer {
     regex: fancy_regex::Regex,
     replace_with: String,
     is_literal: bool,
@@ -245,10 +334,82 @@ impl Replacer<str> for FancyReplacer {
                 only_matched,
             )
         }
+        .map(|(replaced, _made)| replaced)
+    }
+
+    fn replace_bounded<'a>(
+        &'a self,
+        content: &'a str,
+        only_matched: bool,
+        use_color: bool,
+        remaining: &mut usize,
+    ) -> Option<Cow<'a, str>> {
+        if *remaining == 0 {
+            return None;
+        }
+        let regex = &self.regex;
+        let limit = *remaining;
+        let result = if self.is_literal {
+            FancyReplacer::replacen(
+                regex,
+                limit,
+                content,
+                use_color,
+                fancy_regex::NoExpand(&self.replace_with),
+                only_matched,
+            )
+        } else {
+            FancyReplacer::replacen(
+                regex,
+                limit,
+                content,
+                use_color,
+                &*self.replace_with,
+                only_matched,
+            )
+        };
+        if let Some((_, made)) = &result {
+            *remaining -= made;
+        }
+        result.map(|(replaced, _made)| replaced)
     }
 }
 
 impl FancyReplacer {
+    /// Like [`Replacer::replace`], but returns each match's byte range and
+    /// replacement individually instead of the fully-substituted content, for
+    /// `--diff` to render as a unified diff.
+    pub fn changes(&self, haystack: &str) -> Vec<crate::diff::Change> {
+        let limit = self.replacements;
+        let mut changes = Vec::new();
+        for (i, cap) in self.regex.captures_iter(haystack).enumerate() {
+            // Mirror `replacen`'s `cap.ok()?`: a fancy-regex match error
+            // aborts the whole file rather than just skipping one match, so
+            // `--diff` never previews changes that an actual replace run
+            // (which would make none) wouldn't apply.
+            let Ok(cap) = cap else { return Vec::new() };
+            let m = cap.get(0).unwrap();
+            let mut replacement = String::new();
+            if self.is_literal {
+                fancy_regex::NoExpand(&self.replace_with).replace_append(&cap, &mut replacement);
+            } else {
+                (&*self.replace_with).replace_append(&cap, &mut replacement);
+            }
+            changes.push(crate::diff::Change {
+                start: m.start(),
+                end: m.end(),
+                replacement: replacement.into_bytes(),
+            });
+            if limit > 0 && i >= limit - 1 {
+                break;
+            }
+        }
+        changes
+    }
+
+    /// Returns the replaced content alongside the number of replacements
+    /// actually made, so callers sharing a budget across calls (see
+    /// [`Replacer::replace_bounded`]) can track it down.
     fn replacen<'haystack, R: fancy_regex::Replacer>(
         regex: &fancy_regex::Regex,
         limit: usize,
@@ -256,11 +417,12 @@ impl FancyReplacer {
         use_color: bool,
         mut rep: R,
         only_matched: bool,
-    ) -> Option<Cow<'haystack, str>> {
+    ) -> Option<(Cow<'haystack, str>, usize)> {
         let mut it = regex.captures_iter(haystack).enumerate().peekable();
         _ = it.peek()?;
         let mut new = String::new();
         let mut last_match = 0;
+        let mut made = 0;
         for (i, cap) in it {
             // unwrap on 0 is OK because captures only reports matches
             let cap = cap.ok()?;
@@ -280,6 +442,7 @@ impl FancyReplacer {
                 );
             }
             last_match = m.end();
+            made = i + 1;
             if limit > 0 && i >= limit - 1 {
                 break;
             }
@@ -287,6 +450,6 @@ impl FancyReplacer {
         if !only_matched {
             new.push_str(&haystack[last_match..]);
         }
-        Some(Cow::Owned(new))
+        Some((Cow::Owned(new), made))
     }
 }
@@ -0,0 +1,166 @@
+use std::io::Write;
+
+use crate::Result;
+
+use super::Replacer;
+
+/// Process `content` as a sequence of newline-delimited records, replacing
+/// each one with `replacer` and writing the result to `writer` as soon as
+/// it's produced, instead of holding the whole file (content plus output) in
+/// memory at once.
+///
+/// When `multiline` is true the active pattern may match across newlines, so
+/// records can't simply be replaced one at a time: they're accumulated into
+/// a carry buffer instead, and only the portion up to the last safe record
+/// boundary is flushed each round. The unflushed tail is capped at
+/// `max_match_bytes`, so a match straddling a flush boundary is never split.
+/// For the common single-line case the carry stays empty and memory use is
+/// O(record size).
+///
+/// `replacements` is the `-n` limit for the whole file (0 for unlimited): a
+/// single budget is threaded across every flushed chunk so it means the same
+/// thing here as it does for the buffered, whole-file replace path.
+#[allow(clippy::too_many_arguments)]
+pub fn stream_bytes<W: Write>(
+    replacer: &impl Replacer<[u8]>,
+    mut content: &[u8],
+    writer: &mut W,
+    multiline: bool,
+    max_match_bytes: usize,
+    replacements: usize,
+    only_matched: bool,
+    use_color: bool,
+) -> Result<()> {
+    let mut carry: Vec<u8> = Vec::new();
+    let mut remaining = if replacements == 0 { usize::MAX } else { replacements };
+
+    while !content.is_empty() {
+        let record_end = match content.iter().position(|&b| b == b'\n') {
+            Some(i) => i + 1,
+            None => content.len(),
+        };
+        let (record, rest) = content.split_at(record_end);
+        content = rest;
+
+        if !multiline {
+            write_replaced(replacer, record, writer, only_matched, use_color, &mut remaining)?;
+            continue;
+        }
+
+        carry.extend_from_slice(record);
+        if carry.len() <= max_match_bytes {
+            continue;
+        }
+
+        let split_at = last_record_boundary(&carry, carry.len() - max_match_bytes);
+        let (flush, tail) = carry.split_at(split_at);
+        write_replaced(replacer, flush, writer, only_matched, use_color, &mut remaining)?;
+        carry = tail.to_vec();
+    }
+
+    if !carry.is_empty() {
+        write_replaced(replacer, &carry, writer, only_matched, use_color, &mut remaining)?;
+    }
+
+    Ok(())
+}
+
+fn write_replaced<W: Write>(
+    replacer: &impl Replacer<[u8]>,
+    record: &[u8],
+    writer: &mut W,
+    only_matched: bool,
+    use_color: bool,
+    remaining: &mut usize,
+) -> Result<()> {
+    match replacer.replace_bounded(record, only_matched, use_color, remaining) {
+        Some(replaced) => writer.write_all(&replaced)?,
+        None if !only_matched => writer.write_all(record)?,
+        None => {}
+    }
+    Ok(())
+}
+
+fn last_record_boundary(buf: &[u8], at: usize) -> usize {
+    match buf[..at].iter().rposition(|&b| b == b'\n') {
+        Some(i) => i + 1,
+        None => 0,
+    }
+}
+
+/// `str` counterpart of [`stream_bytes`], used by the fancy-regex path.
+///
+/// `replacements` is the `-n` limit for the whole file (0 for unlimited): a
+/// single budget is threaded across every flushed chunk so it means the same
+/// thing here as it does for the buffered, whole-file replace path.
+#[allow(clippy::too_many_arguments)]
+pub fn stream_str<W: Write>(
+    replacer: &impl Replacer<str>,
+    mut content: &str,
+    writer: &mut W,
+    multiline: bool,
+    max_match_bytes: usize,
+    replacements: usize,
+    only_matched: bool,
+    use_color: bool,
+) -> Result<()> {
+    let mut carry = String::new();
+    let mut remaining = if replacements == 0 { usize::MAX } else { replacements };
+
+    while !content.is_empty() {
+        let record_end = match content.find('\n') {
+            Some(i) => i + 1,
+            None => content.len(),
+        };
+        let (record, rest) = content.split_at(record_end);
+        content = rest;
+
+        if !multiline {
+            write_replaced_str(replacer, record, writer, only_matched, use_color, &mut remaining)?;
+            continue;
+        }
+
+        carry.push_str(record);
+        if carry.len() <= max_match_bytes {
+            continue;
+        }
+
+        let split_at = last_record_boundary_str(&carry, carry.len() - max_match_bytes);
+        let flush = carry[..split_at].to_owned();
+        carry.drain(..split_at);
+        write_replaced_str(replacer, &flush, writer, only_matched, use_color, &mut remaining)?;
+    }
+
+    if !carry.is_empty() {
+        write_replaced_str(replacer, &carry, writer, only_matched, use_color, &mut remaining)?;
+    }
+
+    Ok(())
+}
+
+fn write_replaced_str<W: Write>(
+    replacer: &impl Replacer<str>,
+    record: &str,
+    writer: &mut W,
+    only_matched: bool,
+    use_color: bool,
+    remaining: &mut usize,
+) -> Result<()> {
+    match replacer.replace_bounded(record, only_matched, use_color, remaining) {
+        Some(replaced) => writer.write_all(replaced.as_bytes())?,
+        None if !only_matched => writer.write_all(record.as_bytes())?,
+        None => {}
+    }
+    Ok(())
+}
+
+fn last_record_boundary_str(buf: &str, at: usize) -> usize {
+    // `at` may land inside a multi-byte character; walk back to a char
+    // boundary first so the slice below is valid.
+    let at = (0..=at).rev().find(|&i| buf.is_char_boundary(i)).unwrap_or(0);
+    match buf[..at].rfind('\n') {
+        Some(i) => i + 1,
+        None => 0,
+    }
+}
+
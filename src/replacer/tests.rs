@@ -99,3 +99,57 @@ fn no_unescape_literal_replacements() {
 fn full_word_replace() {
     replace("abc", "def", false, Some("w"), "abcd abc", "abcd def");
 }
+
+#[test]
+fn streaming_multiline_match_straddling_max_match_bytes() {
+    const UNLIMITED_REPLACEMENTS: usize = 0;
+
+    // A match that spans a newline, placed right around where the carry
+    // buffer would otherwise be flushed: `max_match_bytes` is set smaller
+    // than the distance from the start of the content to the end of the
+    // match, so the flush has to hold back the whole match instead of
+    // splitting it mid-pattern.
+    let src = "before\nSTART middle END\nafter\n";
+    let max_match_bytes = "before\nSTART middle".len();
+
+    let replacer = RegexReplacer::new(
+        "START.*?END".to_owned(),
+        "REPLACED".to_owned(),
+        false,
+        Some("s".to_owned()),
+        UNLIMITED_REPLACEMENTS,
+    )
+    .unwrap();
+
+    let mut out = Vec::new();
+    stream_bytes(
+        &replacer,
+        src.as_bytes(),
+        &mut out,
+        true,
+        max_match_bytes,
+        UNLIMITED_REPLACEMENTS,
+        false,
+        false,
+    )
+    .unwrap();
+    assert_eq!(
+        std::str::from_utf8(&out).unwrap(),
+        "before\nREPLACED\nafter\n"
+    );
+}
+
+#[test]
+fn streaming_respects_replacement_limit_across_records() {
+    // Three records each containing a match: with `-n 1`, only the very first
+    // occurrence in the whole stream should be replaced, the same as the
+    // non-streaming path. A limit re-applied per record would replace once
+    // per line instead.
+    let src = "foo\nfoo\nfoo\n";
+
+    let replacer = RegexReplacer::new("foo".to_owned(), "bar".to_owned(), false, None, 1).unwrap();
+
+    let mut out = Vec::new();
+    stream_bytes(&replacer, src.as_bytes(), &mut out, false, 64, 1, false, false).unwrap();
+    assert_eq!(std::str::from_utf8(&out).unwrap(), "bar\nfoo\nfoo\n");
+}
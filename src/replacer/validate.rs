@@ -0,0 +1,55 @@
+use std::fmt;
+
+use crate::Result;
+
+/// A `$`-capture reference in a replacement string that is ambiguous because
+/// it's immediately followed by another identifier character, e.g. `$1abc`
+/// (is this capture group 1 followed by `abc`, or capture group `1abc`?).
+#[derive(Debug, PartialEq, Eq)]
+pub struct InvalidReplaceCapture {
+    capture: String,
+}
+
+impl fmt::Display for InvalidReplaceCapture {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "The replacement string contains the capture reference '{}', which is ambiguous \
+             here. Use '${{{}}}' instead.",
+            self.capture,
+            self.capture.trim_start_matches('$')
+        )
+    }
+}
+
+impl std::error::Error for InvalidReplaceCapture {}
+
+/// Ensure `replace_with` contains no ambiguous numbered capture references,
+/// i.e. a `$<digits>` immediately followed by another ident character.
+pub fn validate_replace(replace_with: &str) -> Result<()> {
+    let bytes = replace_with.as_bytes();
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'$' {
+            let start = i;
+            let mut end = i + 1;
+            while end < bytes.len() && bytes[end].is_ascii_digit() {
+                end += 1;
+            }
+            if end > start + 1 {
+                if let Some(&next) = bytes.get(end) {
+                    if next.is_ascii_alphanumeric() || next == b'_' {
+                        return Err(InvalidReplaceCapture {
+                            capture: replace_with[start..end].to_owned(),
+                        }
+                        .into());
+                    }
+                }
+            }
+            i = end.max(i + 1);
+        } else {
+            i += 1;
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,204 @@
+use std::{
+    borrow::Cow,
+    path::{Component, Path, PathBuf},
+};
+
+use globset::{Glob, GlobMatcher, GlobSet, GlobSetBuilder};
+
+use sd::{Error, Result};
+
+pub(crate) struct FileType {
+    pub name: &'static str,
+    pub globs: &'static [&'static str],
+}
+
+/// Built-in `--type` definitions, lexicographically sorted by name.
+pub(crate) const TYPES: &[FileType] = &[
+    FileType { name: "c", globs: &["*.c", "*.h"] },
+    FileType { name: "cpp", globs: &["*.cc", "*.cpp", "*.cxx", "*.hh", "*.hpp"] },
+    FileType { name: "go", globs: &["*.go"] },
+    FileType { name: "java", globs: &["*.java"] },
+    FileType { name: "js", globs: &["*.js", "*.jsx", "*.mjs"] },
+    FileType { name: "json", globs: &["*.json"] },
+    FileType { name: "markdown", globs: &["*.md", "*.markdown"] },
+    FileType { name: "py", globs: &["*.py", "*.pyi"] },
+    FileType { name: "rust", globs: &["*.rs"] },
+    FileType { name: "toml", globs: &["*.toml"] },
+    FileType { name: "ts", globs: &["*.ts", "*.tsx"] },
+    FileType { name: "yaml", globs: &["*.yml", "*.yaml"] },
+];
+
+pub(crate) fn lookup(name: &str) -> Option<&'static FileType> {
+    TYPES.iter().find(|t| t.name == name)
+}
+
+pub(crate) fn print_type_list(writer: &mut impl std::io::Write) -> Result<()> {
+    for ty in TYPES {
+        writeln!(writer, "{}: {}", ty.name, ty.globs.join(", "))?;
+    }
+    Ok(())
+}
+
+/// Resolves `--type`, `--type-not`, and `--glob` into a single predicate over
+/// file paths, applied in the input module before a file is ever mmapped.
+///
+/// `--type`/`--type-not` narrow the set down to (or away from) the union of
+/// globs for the named types. The `--glob` patterns are then layered on top
+/// as an ordered allow/deny list, ripgrep-style: they're evaluated
+/// last-flag-first, and the first one that matches (positive, or negated
+/// with a leading `!`) decides the outcome. If none of them match, the
+/// default is deny when at least one plain (non-negated) `--glob` was given
+/// (it's acting as an allowlist), and allow otherwise (the globs are only
+/// excluding a few paths from an otherwise-unrestricted set).
+pub(crate) struct PathFilter {
+    type_allow: Option<GlobSet>,
+    type_deny: Option<GlobSet>,
+    globs: Vec<(bool, GlobMatcher)>,
+    has_allow_glob: bool,
+}
+
+impl PathFilter {
+    pub(crate) fn new(types: &[String], types_not: &[String], globs: &[String]) -> Result<Self> {
+        let globs = globs
+            .iter()
+            .map(|pattern| {
+                let (is_allow, pattern) = match pattern.strip_prefix('!') {
+                    Some(rest) => (false, rest),
+                    None => (true, pattern.as_str()),
+                };
+                Ok((is_allow, Glob::new(pattern)?.compile_matcher()))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        let has_allow_glob = globs.iter().any(|(is_allow, _)| *is_allow);
+
+        Ok(Self {
+            type_allow: Self::build_type_set(types)?,
+            type_deny: Self::build_type_set(types_not)?,
+            globs,
+            has_allow_glob,
+        })
+    }
+
+    fn build_type_set(names: &[String]) -> Result<Option<GlobSet>> {
+        if names.is_empty() {
+            return Ok(None);
+        }
+
+        let mut builder = GlobSetBuilder::new();
+        for name in names {
+            let ty = lookup(name).ok_or_else(|| Error::UnknownType(name.to_owned()))?;
+            for glob in ty.globs {
+                builder.add(Glob::new(glob)?);
+            }
+        }
+        Ok(Some(builder.build()?))
+    }
+
+    pub(crate) fn matches(&self, path: &Path) -> bool {
+        let path = &*strip_leading_cur_dir(path);
+
+        if let Some(deny) = &self.type_deny {
+            if deny.is_match(path) {
+                return false;
+            }
+        }
+        if let Some(allow) = &self.type_allow {
+            if !allow.is_match(path) {
+                return false;
+            }
+        }
+        for (is_allow, glob) in self.globs.iter().rev() {
+            if glob.is_match(path) {
+                return *is_allow;
+            }
+        }
+        !self.has_allow_glob
+    }
+}
+
+/// Strips leading `./` components so a directory-anchored glob like
+/// `vendor/**` matches paths from a recursive walk rooted at `.` or an
+/// explicit `./vendor/thing.rs` argument the same way it matches `vendor/thing.rs`.
+fn strip_leading_cur_dir(path: &Path) -> Cow<'_, Path> {
+    if path.components().next() != Some(Component::CurDir) {
+        return Cow::Borrowed(path);
+    }
+    Cow::Owned(path.components().filter(|c| *c != Component::CurDir).collect::<PathBuf>())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn filter(types: &[&str], types_not: &[&str], globs: &[&str]) -> PathFilter {
+        let types: Vec<String> = types.iter().map(|&s| s.to_owned()).collect();
+        let types_not: Vec<String> = types_not.iter().map(|&s| s.to_owned()).collect();
+        let globs: Vec<String> = globs.iter().map(|&s| s.to_owned()).collect();
+        PathFilter::new(&types, &types_not, &globs).unwrap()
+    }
+
+    #[test]
+    fn last_glob_wins() {
+        let f = filter(&[], &[], &["*.rs", "!main.rs", "main.rs"]);
+        assert!(f.matches(Path::new("main.rs")));
+        assert!(f.matches(Path::new("lib.rs")));
+        // Nothing in the glob list matches "other.txt", but a plain (allow)
+        // glob was supplied, so it's acting as an allowlist and the default
+        // flips to deny.
+        assert!(!f.matches(Path::new("other.txt")));
+    }
+
+    #[test]
+    fn glob_deny_can_override_earlier_allow() {
+        let f = filter(&[], &[], &["*.rs", "!main.rs"]);
+        assert!(!f.matches(Path::new("main.rs")));
+        assert!(f.matches(Path::new("lib.rs")));
+    }
+
+    #[test]
+    fn allow_only_globs_exclude_everything_else() {
+        // `--glob '*.toml'` with nothing else should restrict to just that
+        // type, not merely add it on top of an unrestricted default.
+        let f = filter(&[], &[], &["*.toml"]);
+        assert!(f.matches(Path::new("Cargo.toml")));
+        assert!(!f.matches(Path::new("main.rs")));
+    }
+
+    #[test]
+    fn deny_only_globs_leave_the_default_allow_in_place() {
+        // `--glob '!vendor/**'` alone should exclude just that subtree,
+        // leaving everything else allowed.
+        let f = filter(&[], &[], &["!vendor/**"]);
+        assert!(!f.matches(Path::new("vendor/thing.rs")));
+        assert!(f.matches(Path::new("src/main.rs")));
+    }
+
+    #[test]
+    fn leading_cur_dir_is_stripped_before_matching_directory_anchored_globs() {
+        let f = filter(&[], &[], &["!vendor/**"]);
+        assert!(!f.matches(Path::new("./vendor/thing.rs")));
+        assert!(f.matches(Path::new("./src/main.rs")));
+    }
+
+    #[test]
+    fn type_deny_beats_type_allow() {
+        // A file matching both --type and --type-not is excluded: deny wins.
+        let f = filter(&["rust"], &["rust"], &[]);
+        assert!(!f.matches(Path::new("main.rs")));
+    }
+
+    #[test]
+    fn type_allow_restricts_to_named_types() {
+        let f = filter(&["rust"], &[], &[]);
+        assert!(f.matches(Path::new("main.rs")));
+        assert!(!f.matches(Path::new("main.py")));
+    }
+
+    #[test]
+    fn type_deny_short_circuits_before_globs_are_checked() {
+        // --type-not is checked before --glob, so a glob allow can't pull a
+        // type-denied file back in.
+        let f = filter(&[], &["rust"], &["main.rs"]);
+        assert!(!f.matches(Path::new("main.rs")));
+    }
+}